@@ -0,0 +1,134 @@
+//! 截图后端：除了默认的 Windows.Graphics.Capture（WGC），还提供两种基于 GDI 的兜底方案，
+//! 用于 WGC 在部分老版本 Windows 10 上不可用，或者目标窗口捕获失败的场景。
+
+use std::ffi::c_void;
+use std::ptr;
+
+use anyhow::{anyhow, Result};
+use windows::Win32::Foundation::HWND;
+use windows::Win32::Graphics::Gdi::{
+    BitBlt, CreateCompatibleDC, CreateDIBSection, DeleteDC, DeleteObject, GetWindowDC, ReleaseDC,
+    SelectObject, BITMAPINFO, BITMAPINFOHEADER, BI_RGB, DIB_RGB_COLORS, SRCCOPY,
+};
+use windows::Win32::UI::WindowsAndMessaging::{PrintWindow, PRINT_WINDOW_FLAGS};
+
+const PW_RENDERFULLCONTENT: PRINT_WINDOW_FLAGS = PRINT_WINDOW_FLAGS(2);
+
+/// 截图后端
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum CaptureBackend {
+    /// 自动探测：优先使用Windows.Graphics.Capture，不可用时回退到PrintWindow
+    #[default]
+    Auto,
+    /// Windows.Graphics.Capture，效果最好，但部分老版本Windows 10不可用
+    WindowsGraphicsCapture,
+    /// GDI BitBlt，速度最快，但无法捕获D3D/硬件加速的内容
+    BitBlt,
+    /// PrintWindow(PW_RENDERFULLCONTENT)，比BitBlt慢（约16ms），可以捕获GPU渲染的内容
+    PrintWindow,
+}
+
+impl CaptureBackend {
+    /// 把`Auto`解析为一个具体的后端，其他取值原样返回
+    pub(crate) fn resolve(self) -> CaptureBackend {
+        match self {
+            CaptureBackend::Auto => {
+                if is_wgc_supported() {
+                    CaptureBackend::WindowsGraphicsCapture
+                } else {
+                    log::info!("当前系统不支持Windows.Graphics.Capture，回退到PrintWindow");
+                    CaptureBackend::PrintWindow
+                }
+            }
+            other => other,
+        }
+    }
+}
+
+/// 探测当前系统是否支持Windows.Graphics.Capture
+fn is_wgc_supported() -> bool {
+    windows::Graphics::Capture::GraphicsCaptureSession::IsSupported().unwrap_or(false)
+}
+
+/// 一帧窗口的原始数据：宽、高、自上而下排列、每像素4字节的BGRA缓冲区
+pub(crate) struct RawFrame {
+    pub width: u32,
+    pub height: u32,
+    pub bgra: Vec<u8>,
+}
+
+/// 用GDI BitBlt抓取整个窗口（含标题栏，不含阴影），无法捕获D3D/硬件加速表面的内容
+pub(crate) fn capture_bitblt(hwnd: isize, width: u32, height: u32) -> Result<RawFrame> {
+    capture_via_gdi(hwnd, width, height, false)
+}
+
+/// 用PrintWindow(PW_RENDERFULLCONTENT)抓取整个窗口，比BitBlt慢（约16ms），但能拿到GPU渲染的内容
+pub(crate) fn capture_print_window(hwnd: isize, width: u32, height: u32) -> Result<RawFrame> {
+    capture_via_gdi(hwnd, width, height, true)
+}
+
+fn capture_via_gdi(hwnd: isize, width: u32, height: u32, use_print_window: bool) -> Result<RawFrame> {
+    let hwnd = HWND(hwnd as *mut c_void);
+    unsafe {
+        let window_dc = GetWindowDC(hwnd);
+        if window_dc.is_invalid() {
+            return Err(anyhow!("获取窗口DC失败"));
+        }
+        let mem_dc = CreateCompatibleDC(window_dc);
+        if mem_dc.is_invalid() {
+            ReleaseDC(hwnd, window_dc);
+            return Err(anyhow!("创建内存DC失败"));
+        }
+
+        let mut bitmap_info = BITMAPINFO {
+            bmiHeader: BITMAPINFOHEADER {
+                biSize: std::mem::size_of::<BITMAPINFOHEADER>() as u32,
+                biWidth: width as i32,
+                // 负高度表示自上而下排列，和windows-capture的帧一致
+                biHeight: -(height as i32),
+                biPlanes: 1,
+                biBitCount: 32,
+                biCompression: BI_RGB.0,
+                ..Default::default()
+            },
+            ..Default::default()
+        };
+        let mut bits: *mut c_void = ptr::null_mut();
+        let bitmap = CreateDIBSection(window_dc, &bitmap_info, DIB_RGB_COLORS, &mut bits, None, 0);
+        let bitmap = match bitmap {
+            Ok(bitmap) if !bits.is_null() => bitmap,
+            _ => {
+                let _ = DeleteDC(mem_dc);
+                ReleaseDC(hwnd, window_dc);
+                return Err(anyhow!("创建DIB节失败"));
+            }
+        };
+        let old_obj = SelectObject(mem_dc, bitmap);
+
+        let ok = if use_print_window {
+            PrintWindow(hwnd, mem_dc, PW_RENDERFULLCONTENT).as_bool()
+        } else {
+            BitBlt(mem_dc, 0, 0, width as i32, height as i32, window_dc, 0, 0, SRCCOPY).is_ok()
+        };
+
+        let result = if ok {
+            let len = (width * height * 4) as usize;
+            let slice = std::slice::from_raw_parts(bits as *const u8, len);
+            Ok(RawFrame {
+                width,
+                height,
+                bgra: slice.to_vec(),
+            })
+        } else if use_print_window {
+            Err(anyhow!("PrintWindow捕获失败"))
+        } else {
+            Err(anyhow!("BitBlt捕获失败"))
+        };
+
+        SelectObject(mem_dc, old_obj);
+        let _ = DeleteObject(bitmap);
+        let _ = DeleteDC(mem_dc);
+        ReleaseDC(hwnd, window_dc);
+        result
+    }
+}
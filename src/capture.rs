@@ -0,0 +1,677 @@
+use std::time::{Duration, Instant};
+
+use anyhow::{anyhow, Result};
+use image::{DynamicImage, RgbaImage};
+use tokio::spawn;
+use tokio::sync::watch;
+use tokio::task::JoinHandle;
+use tokio::time::{sleep, timeout};
+use window_inspector::find::get_hwnd_ref_cache;
+use window_inspector::position_size::{get_client_xywh, get_window_xywh_exclude_shadow};
+use windows_capture::window::Window;
+use windows_capture::{
+    capture::GraphicsCaptureApiHandler, frame::Frame, graphics_capture_api::InternalCaptureControl,
+    settings::Settings,
+};
+
+use crate::backend::{capture_bitblt, capture_print_window, CaptureBackend, RawFrame};
+use crate::dedupe::FrameDeduper;
+use crate::error::{CaptureError, CaptureState};
+use crate::event_hook::{WindowEventListener, WindowLifecycleEvent};
+use crate::options::{CaptureOptions, ColorFormat};
+
+/// 设备丢失/被移除时的DXGI错误码，这类错误是瞬态的，应该立刻重建捕获会话
+const DXGI_ERROR_DEVICE_REMOVED: i32 = 0x887A_0005u32 as i32;
+const DXGI_ERROR_DEVICE_RESET: i32 = 0x887A_0007u32 as i32;
+
+/// 连续立刻重建捕获会话的"设备丢失"次数上限：超过这个次数还在失败，
+/// 说明丢失不是瞬态的（比如根本没有GPU/驱动），改走正常的退避重试，避免干转CPU
+const MAX_CONSECUTIVE_DEVICE_LOST_RETRIES: u32 = 5;
+
+/// 把`Capture::start`失败时返回的错误分类成[`CaptureError`]，区分"设备丢失这种瞬态问题"
+/// 和"WGC初始化失败这种需要正常退避重试的问题"
+fn classify_wgc_start_error(e: &(dyn std::error::Error + 'static)) -> CaptureError {
+    let hr = e
+        .downcast_ref::<windows::core::Error>()
+        .map(|e| e.code().0)
+        .unwrap_or(0);
+    if hr == DXGI_ERROR_DEVICE_REMOVED || hr == DXGI_ERROR_DEVICE_RESET {
+        CaptureError::DeviceLost { hr }
+    } else {
+        CaptureError::WgcInitFailed { hr }
+    }
+}
+
+/// GDI兜底后端（BitBlt/PrintWindow）的轮询间隔，这两个后端没有WGC那样的帧到达事件
+const GDI_CAPTURE_INTERVAL: Duration = Duration::from_millis(33);
+
+/// 计算客户区域相对于完整窗口缓冲区的裁剪矩形：(x, y, w, h)
+/// 这段逻辑在WGC的帧和GDI兜底后端的原始缓冲区之间是通用的
+fn resolve_client_crop(
+    hwnd: isize,
+    border: (u32, u32, u32, u32),
+    frame_w: u32,
+    frame_h: u32,
+) -> Result<(u32, u32, u32, u32)> {
+    let window_xywh = get_window_xywh_exclude_shadow(hwnd)?;
+    if window_xywh.2 != frame_w || window_xywh.3 != frame_h {
+        return Err(CaptureError::SizeMismatch.into());
+    }
+    let client_xywh = get_client_xywh(hwnd)?;
+    if client_xywh.2 == 0 || client_xywh.3 == 0 {
+        return Err(anyhow!("窗口大小为0"));
+    }
+    if client_xywh.0 < window_xywh.0
+        || client_xywh.1 < window_xywh.1
+        || client_xywh.0 + client_xywh.2 as i32 > window_xywh.0 + window_xywh.2 as i32
+        || client_xywh.1 + client_xywh.3 as i32 > window_xywh.1 + window_xywh.3 as i32
+    {
+        return Err(anyhow!("客户区域超出窗口范围"));
+    };
+    let client_xy_in_window = (
+        (client_xywh.0 - window_xywh.0) as u32,
+        (client_xywh.1 - window_xywh.1) as u32,
+    );
+    if client_xywh.2 < border.0 + border.2 || client_xywh.3 < border.1 + border.3 {
+        return Err(anyhow!("客户区域小于边框大小"));
+    }
+    Ok((
+        client_xy_in_window.0 + border.0,
+        client_xy_in_window.1 + border.1,
+        client_xywh.2 - border.0 - border.2,
+        client_xywh.3 - border.1 - border.3,
+    ))
+}
+
+/// 把GDI兜底后端抓到的原始BGRA缓冲区裁剪成客户区域图像
+/// GDI的DIB节天然就是BGRA顺序：请求`Bgra8`时原样拷贝，不需要额外的通道交换；
+/// 只有请求`Rgba8`时才需要把每个像素的R、B通道换过来
+fn raw_frame_to_img(
+    hwnd: isize,
+    border: (u32, u32, u32, u32),
+    color_format: ColorFormat,
+    raw: &RawFrame,
+) -> Result<DynamicImage> {
+    let (x, y, w, h) = resolve_client_crop(hwnd, border, raw.width, raw.height)?;
+    let mut buffer = Vec::with_capacity((w * h * 4) as usize);
+    for row in y..y + h {
+        let row_start = ((row * raw.width + x) * 4) as usize;
+        let row_end = row_start + (w * 4) as usize;
+        for px in raw.bgra[row_start..row_end].chunks_exact(4) {
+            match color_format {
+                ColorFormat::Rgba8 => buffer.extend_from_slice(&[px[2], px[1], px[0], px[3]]),
+                ColorFormat::Bgra8 => buffer.extend_from_slice(px),
+            }
+        }
+    }
+    let img = RgbaImage::from_raw(w, h, buffer).ok_or_else(|| anyhow!("转换为RgbaImage失败"))?;
+    Ok(DynamicImage::ImageRgba8(img))
+}
+
+struct CaptureMessage {
+    // 暂停watch
+    pause_rx: watch::Receiver<bool>,
+    // 停止watch
+    stop_rx: watch::Receiver<bool>,
+    // 换窗口watch，变为true时强制结束当前捕获，让外层循环重新解析句柄
+    rebind_rx: watch::Receiver<bool>,
+    // 窗口句柄
+    hwnd: isize,
+    // 额外需要去除的边框：左上右下
+    border: (u32, u32, u32, u32),
+    // 图像发送者
+    img_tx: watch::Sender<Option<(DynamicImage, Instant)>>,
+    // 状态/错误发送者
+    state_tx: watch::Sender<CaptureState>,
+    // 输出图像的颜色通道顺序，决定`to_img`要不要做通道交换
+    color_format: ColorFormat,
+    // 是否开启重复帧抑制，以及画面最后一次实际变化的时间发送者
+    deduper: Option<FrameDeduper>,
+    // hwnd被销毁事件的计数器，以及创建本条消息时的基准值：
+    // 计数器变化说明目标窗口在本次捕获会话开始之后被销毁了，需要立刻结束会话
+    destroy_rx: watch::Receiver<u64>,
+    destroy_baseline: u64,
+}
+
+struct Capture {
+    message: CaptureMessage,
+}
+
+impl Capture {
+    fn to_img(&self, frame: &mut Frame) -> Result<DynamicImage> {
+        let (x, y, w, h) = resolve_client_crop(
+            self.message.hwnd,
+            self.message.border,
+            frame.width(),
+            frame.height(),
+        )?;
+        let buffer = frame
+            .buffer_crop(x, y, x + w, y + h)?
+            .as_raw_nopadding_buffer()?
+            .to_vec();
+        // WGC已经被`Settings`里的`options.color_format.to_wgc()`告知要输出哪种通道顺序，
+        // 拿到的`buffer`本身就是该顺序，原样放进图像，不需要额外的通道交换
+        // （和GDI兜底后端一致：请求`Bgra8`时图像里的字节就是BGRA顺序）
+        let img = RgbaImage::from_raw(w, h, buffer).ok_or_else(|| anyhow!("转换为RgbaImage失败"))?;
+        Ok(DynamicImage::ImageRgba8(img))
+    }
+}
+
+impl GraphicsCaptureApiHandler for Capture {
+    type Flags = CaptureMessage;
+
+    type Error = Box<dyn std::error::Error + Send + Sync>;
+
+    fn new(message: Self::Flags) -> Result<Self, Self::Error> {
+        Ok(Self { message })
+    }
+
+    fn on_frame_arrived(
+        &mut self,
+        frame: &mut Frame,
+        capture_control: InternalCaptureControl,
+    ) -> Result<(), Self::Error> {
+        // 停止
+        if *self.message.stop_rx.borrow() {
+            capture_control.stop();
+            return Ok(());
+        }
+
+        // 换窗口，结束当前捕获，让外层循环用新的类名/标题重新解析句柄
+        if *self.message.rebind_rx.borrow() {
+            capture_control.stop();
+            return Ok(());
+        }
+
+        // 目标窗口已经被销毁，不等WGC自己检测到，立刻结束本次捕获会话，
+        // 让外层循环去等一个匹配的EVENT_OBJECT_CREATE再重新解析句柄
+        if *self.message.destroy_rx.borrow() != self.message.destroy_baseline {
+            log::warn!("目标窗口已销毁，结束当前捕获会话");
+            capture_control.stop();
+            return Ok(());
+        }
+
+        // 暂停
+        if *self.message.pause_rx.borrow() {
+            return Ok(());
+        }
+
+        match self.to_img(frame) {
+            Ok(img) => {
+                let should_send = match self.message.deduper.as_mut() {
+                    Some(deduper) => deduper.should_send(img.as_bytes()),
+                    None => true,
+                };
+                if !should_send {
+                    return Ok(());
+                }
+                if let Err(e) = self.message.img_tx.send(Some((img, Instant::now()))) {
+                    log::warn!("发送图像失败: {}", e);
+                    return Ok(());
+                }
+                self.message.state_tx.send(CaptureState::Capturing).ok();
+            }
+            Err(e) => {
+                log::warn!("转换图像失败: {}", e);
+                if let Some(capture_error) = e.downcast_ref::<CaptureError>() {
+                    self.message.state_tx.send(CaptureState::Stalled(*capture_error)).ok();
+                    // 窗口大小变化导致WGC的帧缓冲区暂时跟不上最新的窗口大小：
+                    // 与其一直丢帧等它自己追上，不如直接结束本次捕获，
+                    // 让外层循环用EVENT_OBJECT_LOCATIONCHANGE之后最新的窗口大小重新建立捕获会话
+                    if *capture_error == CaptureError::SizeMismatch {
+                        capture_control.stop();
+                    }
+                }
+                return Ok(());
+            }
+        };
+
+        Ok(())
+    }
+
+    fn on_closed(&mut self) -> Result<(), Self::Error> {
+        log::debug!("捕获对象已不存在");
+        Ok(())
+    }
+}
+
+pub struct ClientCapture {
+    window_tx: watch::Sender<(String, String)>,
+    window_rx: watch::Receiver<(String, String)>,
+    border: (u32, u32, u32, u32),
+    pause_tx: watch::Sender<bool>,
+    pause_rx: watch::Receiver<bool>,
+    stop_tx: watch::Sender<bool>,
+    stop_rx: watch::Receiver<bool>,
+    rebind_tx: watch::Sender<bool>,
+    rebind_rx: watch::Receiver<bool>,
+    img_tx: watch::Sender<Option<(DynamicImage, Instant)>>,
+    img_rx: watch::Receiver<Option<(DynamicImage, Instant)>>,
+    state_tx: watch::Sender<CaptureState>,
+    state_rx: watch::Receiver<CaptureState>,
+    change_tx: watch::Sender<Instant>,
+    change_rx: watch::Receiver<Instant>,
+    capture_handle: Option<JoinHandle<()>>,
+    /// 可以接受的截图延时
+    delay: Duration,
+    /// 截图后端
+    backend: CaptureBackend,
+    /// 光标/边框/颜色格式等截图选项
+    options: CaptureOptions,
+}
+
+impl ClientCapture {
+    /// 创建一个新的截图对象
+    /// # 参数
+    /// - window_class: 窗口类名
+    /// - window_title: 窗口标题
+    /// - border: 在客户区域的基础上额外需要去除的边框：左上右下
+    /// - delay: 可以接受的截图延时，默认50ms
+    /// - backend: 截图后端，默认`CaptureBackend::Auto`
+    /// - options: 光标/边框/颜色格式等截图选项，默认见[`CaptureOptions::default`]。
+    ///   开启`dedupe`时如果`heartbeat_interval`不小于`delay`会被自动收紧为`delay`的一半，
+    ///   否则画面静止超过`delay`就会被`get_img`/`snapshot`误报"图像已过期"
+    pub fn new(
+        window_class: String,
+        window_title: String,
+        border: Option<(u32, u32, u32, u32)>,
+        delay: Option<Duration>,
+        backend: Option<CaptureBackend>,
+        options: Option<CaptureOptions>,
+    ) -> Self {
+        let (window_tx, window_rx) = watch::channel((window_class, window_title));
+        let (pause_tx, pause_rx) = watch::channel(false);
+        let (stop_tx, stop_rx) = watch::channel(false);
+        let (rebind_tx, rebind_rx) = watch::channel(false);
+        let (img_tx, img_rx) = watch::channel(None);
+        let (state_tx, state_rx) = watch::channel(CaptureState::default());
+        let (change_tx, change_rx) = watch::channel(Instant::now());
+        let delay = delay.unwrap_or(Duration::from_millis(50));
+        let mut options = options.unwrap_or_default();
+        if options.dedupe && options.heartbeat_interval >= delay {
+            // heartbeat_interval如果不小于delay，画面静止超过delay就会先被get_img/snapshot
+            // 判定为"图像已过期"，heartbeat还没来得及补发新的时间戳，dedupe反而制造了它本该避免的误报
+            log::warn!(
+                "heartbeat_interval({:?})不小于delay({:?})，会导致画面静止时get_img/snapshot误报图像已过期，已自动收紧为delay的一半",
+                options.heartbeat_interval,
+                delay
+            );
+            options.heartbeat_interval = delay / 2;
+        }
+        Self {
+            window_tx,
+            window_rx,
+            border: border.unwrap_or((0, 0, 0, 0)),
+            pause_tx,
+            pause_rx,
+            stop_tx,
+            stop_rx,
+            rebind_tx,
+            rebind_rx,
+            img_tx,
+            img_rx,
+            state_tx,
+            state_rx,
+            change_tx,
+            change_rx,
+            capture_handle: None,
+            delay,
+            backend: backend.unwrap_or_default(),
+            options,
+        }
+    }
+
+    /// 启动截图线程
+    /// # 返回
+    /// - Ok: 成功
+    /// - Err: 如果截图线程正在运行，则返回错误
+    pub fn start(&mut self) -> Result<()> {
+        if self.is_running() {
+            return Err(anyhow!("截图线程正在运行"));
+        }
+        self.stop_tx.send(false).unwrap();
+        self.pause_tx.send(false).unwrap();
+        self.rebind_tx.send(false).unwrap();
+        let window_rx = self.window_rx.clone();
+        let pause_rx = self.pause_rx.clone();
+        let stop_rx = self.stop_rx.clone();
+        let rebind_tx = self.rebind_tx.clone();
+        let rebind_rx = self.rebind_rx.clone();
+        let img_tx = self.img_tx.clone();
+        let state_tx = self.state_tx.clone();
+        let change_tx = self.change_tx.clone();
+        let border = self.border;
+        let backend = self.backend.resolve();
+        let options = self.options;
+        let capture_handle = spawn(async move {
+            // 监听窗口创建/销毁/位置变化/前台切换，代替固定500ms轮询，
+            // 让"窗口被创建/恢复"能立刻触发重新解析句柄，而不是最多等500ms
+            let event_listener = WindowEventListener::spawn();
+            // current_hwnd记录当前绑定的句柄，用来判断Destroyed/LocationChanged事件是不是冲着
+            // 正在捕获的这个窗口来的；retry_rx在Created/ForegroundChanged时被唤醒，
+            // 代替固定500ms轮询；destroy_tx在目标窗口被销毁时计数+1，捕获会话据此立刻结束
+            let (current_hwnd_tx, current_hwnd_rx) = watch::channel(0isize);
+            let (retry_tx, mut retry_rx) = watch::channel(false);
+            let (destroy_tx, destroy_rx) = watch::channel(0u64);
+            spawn(dispatch_window_events(
+                event_listener,
+                current_hwnd_rx,
+                retry_tx,
+                destroy_tx,
+                stop_rx.clone(),
+            ));
+            let mut consecutive_device_lost = 0u32;
+            loop {
+                if *stop_rx.borrow() {
+                    break;
+                }
+                // 每次外层循环都重新读取类名/标题，这样change_window之后可以立即生效
+                let (window_class, window_title) = window_rx.borrow().clone();
+                match get_hwnd_ref_cache(&window_class, &window_title) {
+                    Ok(hwnd) => {
+                        current_hwnd_tx.send(hwnd).ok();
+                        match backend {
+                            CaptureBackend::WindowsGraphicsCapture => {
+                                let message = CaptureMessage {
+                                    pause_rx: pause_rx.clone(),
+                                    stop_rx: stop_rx.clone(),
+                                    rebind_rx: rebind_rx.clone(),
+                                    hwnd,
+                                    border,
+                                    img_tx: img_tx.clone(),
+                                    state_tx: state_tx.clone(),
+                                    color_format: options.color_format,
+                                    deduper: options
+                                        .dedupe
+                                        .then(|| FrameDeduper::new(options.heartbeat_interval, change_tx.clone())),
+                                    destroy_rx: destroy_rx.clone(),
+                                    destroy_baseline: *destroy_rx.borrow(),
+                                };
+                                let window = Window::from_raw_hwnd(hwnd);
+                                let settings = Settings::new(
+                                    window,
+                                    options.cursor_capture_settings(),
+                                    options.draw_border_settings(),
+                                    options.color_format.to_wgc(),
+                                    message,
+                                );
+                                match Capture::start(settings) {
+                                    Ok(()) => {
+                                        // 这次会话真的跑起来过，说明设备丢失（如果有的话）已经恢复了
+                                        consecutive_device_lost = 0;
+                                    }
+                                    Err(e) => {
+                                        let capture_error = classify_wgc_start_error(e.as_ref());
+                                        log::warn!("截图失败: {}", capture_error);
+                                        state_tx.send(CaptureState::Stalled(capture_error)).ok();
+                                        match capture_error {
+                                            // 设备丢失通常是瞬态的，立刻重建捕获会话，而不是傻等500ms；
+                                            // 但连续失败太多次说明丢失不是瞬态的（比如根本没有GPU/驱动），
+                                            // 这时候再立刻重试只是在干转CPU，改走正常的退避
+                                            CaptureError::DeviceLost { .. }
+                                                if consecutive_device_lost
+                                                    < MAX_CONSECUTIVE_DEVICE_LOST_RETRIES =>
+                                            {
+                                                consecutive_device_lost += 1;
+                                            }
+                                            CaptureError::DeviceLost { .. } => {
+                                                wait_for_retry_signal(&mut retry_rx).await;
+                                            }
+                                            _ => wait_for_retry_signal(&mut retry_rx).await,
+                                        }
+                                    }
+                                }
+                                log::info!("截图线程结束");
+                            }
+                            CaptureBackend::BitBlt | CaptureBackend::PrintWindow => {
+                                run_gdi_backend(
+                                    hwnd,
+                                    backend,
+                                    border,
+                                    options.color_format,
+                                    pause_rx.clone(),
+                                    stop_rx.clone(),
+                                    rebind_rx.clone(),
+                                    img_tx.clone(),
+                                    state_tx.clone(),
+                                    options.dedupe.then(|| {
+                                        FrameDeduper::new(options.heartbeat_interval, change_tx.clone())
+                                    }),
+                                )
+                                .await;
+                            }
+                            CaptureBackend::Auto => unreachable!("resolve()已经把Auto替换为具体后端"),
+                        }
+                        // 消费掉换窗口信号，避免下一轮刚启动就又被立即打断
+                        rebind_tx.send(false).ok();
+                    }
+                    Err(e) => {
+                        log::warn!("获取窗口句柄失败: {}", e);
+                        state_tx.send(CaptureState::Stalled(CaptureError::WindowGone)).ok();
+                        wait_for_retry_signal(&mut retry_rx).await;
+                    }
+                }
+            }
+        });
+        self.capture_handle = Some(capture_handle);
+        Ok(())
+    }
+
+    /// 更换捕获的目标窗口，不需要重建整个对象
+    /// 如果截图线程正在运行，会强制结束当前捕获并用新的类名/标题重新解析句柄
+    /// # 参数
+    /// - window_class: 窗口类名
+    /// - window_title: 窗口标题
+    pub fn change_window(&mut self, window_class: String, window_title: String) {
+        self.window_tx.send((window_class, window_title)).ok();
+        self.rebind_tx.send(true).ok();
+    }
+
+    /// 是否正在运行
+    pub fn is_running(&self) -> bool {
+        if let Some(handle) = self.capture_handle.as_ref() {
+            !handle.is_finished()
+        } else {
+            false
+        }
+    }
+
+    /// 暂停截图，在不需要截图的时候可以暂停，也许可以减少资源占用
+    pub fn pause(&self) {
+        self.pause_tx.send(true).unwrap();
+        self.state_tx.send(CaptureState::Paused).ok();
+    }
+
+    /// 恢复截图
+    pub fn resume(&self) {
+        self.pause_tx.send(false).unwrap();
+    }
+
+    /// 停止截图，把Capture线程关掉
+    pub fn stop(&self) {
+        self.stop_tx.send(true).unwrap();
+    }
+
+    /// 获取图像
+    /// # 返回
+    /// - Ok: 图像
+    /// - Err: 图像为空或者截图时间距离现在超过`delay`
+    pub fn get_img(&mut self) -> Result<DynamicImage> {
+        let img = self.img_rx.borrow().clone();
+        if let Some((img, time)) = img {
+            if time.elapsed() > self.delay {
+                Err(CaptureError::Expired.into())
+            } else {
+                Ok(img)
+            }
+        } else {
+            Err(CaptureError::Empty.into())
+        }
+    }
+
+    /// 获取最新的捕获状态/错误，可以用来观察捕获为什么卡住了
+    pub fn state_rx(&self) -> watch::Receiver<CaptureState> {
+        self.state_rx.clone()
+    }
+
+    /// 获取画面最后一次实际发生变化的时间，仅在开启`CaptureOptions::dedupe`时有意义
+    pub fn change_rx(&self) -> watch::Receiver<Instant> {
+        self.change_rx.clone()
+    }
+
+    /// 等待下一帧新鲜的图像，不同于`get_img`的非阻塞轮询，这个方法会一直等到有新的一帧到达
+    /// # 返回
+    /// - Ok: 图像和它被捕获时的时刻
+    /// - Err: 截图线程已经退出，不会再有新的图像
+    pub async fn next_frame(&mut self) -> Result<(DynamicImage, Instant)> {
+        // 把调用前已经到达的帧标记为已读，`changed()`只在之后有新帧到达时才返回
+        self.img_rx.borrow_and_update();
+        loop {
+            self.img_rx
+                .changed()
+                .await
+                .map_err(|_| anyhow!("截图线程已退出"))?;
+            if let Some(frame) = self.img_rx.borrow().clone() {
+                return Ok(frame);
+            }
+        }
+    }
+
+    /// 拍一张快照：如果当前是暂停状态会先恢复截图，然后在`delay`内等待恰好一帧新的图像
+    /// # 返回
+    /// - Ok: 图像
+    /// - Err: 截图线程已经退出，或者在`delay`内没有等到新的图像
+    pub async fn snapshot(&mut self) -> Result<DynamicImage> {
+        self.resume();
+        let (img, _) = timeout(self.delay, self.next_frame())
+            .await
+            .map_err(|_| anyhow!("等待截图超时"))??;
+        Ok(img)
+    }
+}
+
+/// 等待下一个可能意味着"目标窗口值得重试"的生命周期事件（创建/前台切换），
+/// 500ms作为兜底超时，避免事件监听线程出问题时又退化回死等
+async fn wait_for_retry_signal(retry_rx: &mut watch::Receiver<bool>) {
+    tokio::select! {
+        _ = sleep(Duration::from_millis(500)) => {}
+        _ = retry_rx.changed() => {}
+    }
+}
+
+/// 在后台持续消费`WindowEventListener`的事件，转发成两路watch信号：
+/// - retry_tx: 任意窗口被创建/前台切换，`wait_for_retry_signal`据此提前醒来，不用等满500ms
+/// - destroy_tx: 当前绑定的句柄（由`current_hwnd_rx`跟踪）被销毁，捕获会话应该立刻结束
+/// 由于`WindowEventListener`只有一个接收端，这个转发任务是唯一消费事件的地方
+/// 必须和`stop_rx`一起select：`listener`的消息泵线程只在`WindowEventListener`被drop时才退出，
+/// 不随`stop_rx`一起结束的话，每次`start()`都会泄漏一个挂着`SetWinEventHook`的线程
+async fn dispatch_window_events(
+    mut listener: WindowEventListener,
+    current_hwnd_rx: watch::Receiver<isize>,
+    retry_tx: watch::Sender<bool>,
+    destroy_tx: watch::Sender<u64>,
+    mut stop_rx: watch::Receiver<bool>,
+) {
+    if *stop_rx.borrow() {
+        return;
+    }
+    loop {
+        tokio::select! {
+            _ = stop_rx.changed() => {
+                if *stop_rx.borrow() {
+                    return;
+                }
+            }
+            event = listener.recv() => {
+                match event {
+                    Some((_, WindowLifecycleEvent::Created | WindowLifecycleEvent::ForegroundChanged)) => {
+                        retry_tx.send_modify(|v| *v = !*v);
+                    }
+                    Some((hwnd, WindowLifecycleEvent::Destroyed)) if hwnd == *current_hwnd_rx.borrow() => {
+                        destroy_tx.send_modify(|count| *count = count.wrapping_add(1));
+                    }
+                    // LocationChanged不需要单独转发：`Capture::to_img`每一帧都会重新查询窗口的最新几何信息，
+                    // 一旦和帧缓冲区尺寸对不上就是`CaptureError::SizeMismatch`，
+                    // `on_frame_arrived`会据此结束本次捕获会话，外层循环用窗口当前的大小重新建立会话
+                    Some(_) => {}
+                    // 监听线程退出（理论上只会在WindowEventListener被drop时发生）
+                    None => return,
+                }
+            }
+        }
+    }
+}
+
+/// BitBlt/PrintWindow后端的捕获循环：没有WGC那样的事件驱动，按固定间隔轮询
+/// 一旦目标窗口消失或者需要换窗口/停止，就返回，交回外层循环重新解析句柄
+async fn run_gdi_backend(
+    hwnd: isize,
+    backend: CaptureBackend,
+    border: (u32, u32, u32, u32),
+    color_format: ColorFormat,
+    pause_rx: watch::Receiver<bool>,
+    stop_rx: watch::Receiver<bool>,
+    rebind_rx: watch::Receiver<bool>,
+    img_tx: watch::Sender<Option<(DynamicImage, Instant)>>,
+    state_tx: watch::Sender<CaptureState>,
+    mut deduper: Option<FrameDeduper>,
+) {
+    let mut ticker = tokio::time::interval(GDI_CAPTURE_INTERVAL);
+    loop {
+        ticker.tick().await;
+        if *stop_rx.borrow() || *rebind_rx.borrow() {
+            return;
+        }
+        if *pause_rx.borrow() {
+            continue;
+        }
+        let window_xywh = match get_window_xywh_exclude_shadow(hwnd) {
+            Ok(xywh) => xywh,
+            Err(e) => {
+                log::warn!("获取窗口大小失败: {}", e);
+                state_tx.send(CaptureState::Stalled(CaptureError::WindowGone)).ok();
+                return;
+            }
+        };
+        let raw = match backend {
+            CaptureBackend::BitBlt => capture_bitblt(hwnd, window_xywh.2, window_xywh.3),
+            CaptureBackend::PrintWindow => capture_print_window(hwnd, window_xywh.2, window_xywh.3),
+            _ => unreachable!("只会以BitBlt或PrintWindow调用"),
+        };
+        match raw.and_then(|raw| raw_frame_to_img(hwnd, border, color_format, &raw)) {
+            Ok(img) => {
+                let should_send = match deduper.as_mut() {
+                    Some(deduper) => deduper.should_send(img.as_bytes()),
+                    None => true,
+                };
+                if !should_send {
+                    continue;
+                }
+                if let Err(e) = img_tx.send(Some((img, Instant::now()))) {
+                    log::warn!("发送图像失败: {}", e);
+                    continue;
+                }
+                state_tx.send(CaptureState::Capturing).ok();
+            }
+            Err(e) => {
+                log::warn!("转换图像失败: {}", e);
+                if let Some(capture_error) = e.downcast_ref::<CaptureError>() {
+                    state_tx.send(CaptureState::Stalled(*capture_error)).ok();
+                }
+            }
+        }
+    }
+}
+
+impl Drop for ClientCapture {
+    fn drop(&mut self) {
+        self.stop();
+        if let Some(handle) = self.capture_handle.take() {
+            spawn(async move {
+                if let Err(e) = handle.await {
+                    log::warn!("截图线程异常结束: {}", e);
+                }
+            });
+        }
+    }
+}
@@ -0,0 +1,55 @@
+//! 基于内容哈希的重复帧抑制。
+//! 画面没有实际变化时跳过`img_tx.send`，下游消费者只在画面真的变了的时候才会被唤醒，
+//! 对大部分时间静止的窗口能明显减少CPU和内存分配。
+//! 为了不让`get_img`在画面长时间静止时误报"图像已过期"，仍然会按配置的心跳间隔发送。
+
+use std::time::{Duration, Instant};
+
+use tokio::sync::watch;
+
+/// 对裁剪后的客户区域图像做一次快速哈希（FNV-1a），用来判断画面是否发生了实际变化
+pub(crate) fn hash_frame(buffer: &[u8]) -> u64 {
+    const FNV_OFFSET_BASIS: u64 = 0xcbf2_9ce4_8422_2325;
+    const FNV_PRIME: u64 = 0x0000_0100_0000_01b3;
+    let mut hash = FNV_OFFSET_BASIS;
+    for byte in buffer {
+        hash ^= *byte as u64;
+        hash = hash.wrapping_mul(FNV_PRIME);
+    }
+    hash
+}
+
+/// 记录上一次发送的帧的哈希和发送时间，判断这一帧是不是应该被发送
+pub(crate) struct FrameDeduper {
+    heartbeat_interval: Duration,
+    change_tx: watch::Sender<Instant>,
+    last_hash: Option<u64>,
+    last_sent_at: Instant,
+}
+
+impl FrameDeduper {
+    pub(crate) fn new(heartbeat_interval: Duration, change_tx: watch::Sender<Instant>) -> Self {
+        Self {
+            heartbeat_interval,
+            change_tx,
+            last_hash: None,
+            last_sent_at: Instant::now(),
+        }
+    }
+
+    /// 判断这一帧是否应该被发送：内容和上一次发送的不一样，或者距离上一次发送已经超过心跳间隔
+    pub(crate) fn should_send(&mut self, buffer: &[u8]) -> bool {
+        let hash = hash_frame(buffer);
+        let changed = self.last_hash != Some(hash);
+        if changed {
+            self.last_hash = Some(hash);
+            self.change_tx.send(Instant::now()).ok();
+        }
+        if changed || self.last_sent_at.elapsed() >= self.heartbeat_interval {
+            self.last_sent_at = Instant::now();
+            true
+        } else {
+            false
+        }
+    }
+}
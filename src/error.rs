@@ -0,0 +1,56 @@
+//! 结构化的捕获错误/状态。
+//! 以前`Capture::start`失败和转换帧失败都被吞进`log::warn!`，
+//! 调用方只能从`get_img`拿到一个`anyhow`字符串，不知道捕获到底为什么卡住了。
+//! 配合[`crate::ClientCapture::state_rx`]可以观察到最新的状态/错误。
+
+use std::fmt;
+
+/// 捕获过程中可能出现的错误
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CaptureError {
+    /// Windows.Graphics.Capture初始化失败，附带原始的HRESULT
+    WgcInitFailed { hr: i32 },
+    /// GPU设备丢失/被移除（D3D设备失效），正在自动重建捕获会话
+    DeviceLost { hr: i32 },
+    /// 目标窗口已经不存在了
+    WindowGone,
+    /// 窗口大小与帧大小不一致
+    SizeMismatch,
+    /// 图像已过期
+    Expired,
+    /// 图像为空，还没有捕获到第一帧
+    Empty,
+}
+
+impl fmt::Display for CaptureError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            CaptureError::WgcInitFailed { hr } => {
+                write!(f, "Windows.Graphics.Capture初始化失败: 0x{:08X}", *hr as u32)
+            }
+            CaptureError::DeviceLost { hr } => {
+                write!(f, "捕获设备已丢失，正在自动重建: 0x{:08X}", *hr as u32)
+            }
+            CaptureError::WindowGone => write!(f, "目标窗口已经不存在"),
+            CaptureError::SizeMismatch => write!(f, "窗口大小与帧大小不一致"),
+            CaptureError::Expired => write!(f, "图像已过期"),
+            CaptureError::Empty => write!(f, "图像为空"),
+        }
+    }
+}
+
+impl std::error::Error for CaptureError {}
+
+/// 捕获当前所处的状态
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum CaptureState {
+    /// 还没有启动过
+    #[default]
+    Idle,
+    /// 正常捕获中
+    Capturing,
+    /// 已暂停
+    Paused,
+    /// 因为某个错误停滞，捕获循环正在自动重试/重建
+    Stalled(CaptureError),
+}
@@ -0,0 +1,128 @@
+//! 基于`SetWinEventHook`的窗口生命周期监听。
+//! 用来替代/辅助固定间隔的轮询：窗口被创建、销毁、移动/改变大小、前台切换时都能立刻收到通知，
+//! 而不用等到下一次500ms的重试。Hook的回调运行在一个专门跑消息泵的线程上，
+//! 通过channel把事件转发给tokio任务。
+
+use std::cell::RefCell;
+use std::thread;
+
+use tokio::sync::mpsc;
+use windows::Win32::Foundation::{HWND, LPARAM, WPARAM};
+use windows::Win32::System::Threading::GetCurrentThreadId;
+use windows::Win32::UI::Accessibility::{SetWinEventHook, UnhookWinEvent, HWINEVENTHOOK};
+use windows::Win32::UI::WindowsAndMessaging::{
+    DispatchMessageW, GetMessageW, PostThreadMessageW, TranslateMessage, EVENT_OBJECT_CREATE,
+    EVENT_OBJECT_DESTROY, EVENT_OBJECT_LOCATIONCHANGE, EVENT_SYSTEM_FOREGROUND, MSG, OBJID_WINDOW,
+    WINEVENT_OUTOFCONTEXT, WM_QUIT,
+};
+
+/// 目标窗口（或任意窗口，视具体事件而定）在生命周期中的关键事件
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum WindowLifecycleEvent {
+    /// 有窗口被创建，调用方应该重新尝试用类名/标题解析目标句柄
+    Created,
+    /// 一个窗口被销毁
+    Destroyed,
+    /// 一个窗口的位置或大小发生了变化
+    LocationChanged,
+    /// 前台窗口发生了切换
+    ForegroundChanged,
+}
+
+thread_local! {
+    static EVENT_TX: RefCell<Option<mpsc::UnboundedSender<(isize, WindowLifecycleEvent)>>> =
+        const { RefCell::new(None) };
+}
+
+unsafe extern "system" fn win_event_proc(
+    _hook: HWINEVENTHOOK,
+    event: u32,
+    hwnd: HWND,
+    id_object: i32,
+    _id_child: i32,
+    _id_event_thread: u32,
+    _dwms_event_time: u32,
+) {
+    if id_object != OBJID_WINDOW.0 {
+        return;
+    }
+    let lifecycle_event = match event {
+        EVENT_OBJECT_CREATE => WindowLifecycleEvent::Created,
+        EVENT_OBJECT_DESTROY => WindowLifecycleEvent::Destroyed,
+        EVENT_OBJECT_LOCATIONCHANGE => WindowLifecycleEvent::LocationChanged,
+        EVENT_SYSTEM_FOREGROUND => WindowLifecycleEvent::ForegroundChanged,
+        _ => return,
+    };
+    EVENT_TX.with(|tx| {
+        if let Some(tx) = tx.borrow().as_ref() {
+            tx.send((hwnd.0 as isize, lifecycle_event)).ok();
+        }
+    });
+}
+
+unsafe fn install_hook(event: u32) -> HWINEVENTHOOK {
+    SetWinEventHook(event, event, None, Some(win_event_proc), 0, 0, WINEVENT_OUTOFCONTEXT)
+}
+
+/// 在专门的线程上跑消息泵，监听窗口生命周期事件
+pub(crate) struct WindowEventListener {
+    rx: mpsc::UnboundedReceiver<(isize, WindowLifecycleEvent)>,
+    hook_thread_id: u32,
+    hook_thread: Option<thread::JoinHandle<()>>,
+}
+
+impl WindowEventListener {
+    /// 启动监听线程
+    pub(crate) fn spawn() -> Self {
+        let (tx, rx) = mpsc::unbounded_channel();
+        let (thread_id_tx, thread_id_rx) = std::sync::mpsc::channel();
+        let hook_thread = thread::spawn(move || {
+            EVENT_TX.with(|cell| *cell.borrow_mut() = Some(tx));
+            thread_id_tx.send(unsafe { GetCurrentThreadId() }).ok();
+
+            let hooks = [
+                unsafe { install_hook(EVENT_OBJECT_CREATE) },
+                unsafe { install_hook(EVENT_OBJECT_DESTROY) },
+                unsafe { install_hook(EVENT_OBJECT_LOCATIONCHANGE) },
+                unsafe { install_hook(EVENT_SYSTEM_FOREGROUND) },
+            ];
+
+            let mut msg = MSG::default();
+            unsafe {
+                while GetMessageW(&mut msg, None, 0, 0).as_bool() {
+                    let _ = TranslateMessage(&msg);
+                    DispatchMessageW(&msg);
+                }
+            }
+
+            for hook in hooks {
+                let _ = UnhookWinEvent(hook);
+            }
+        });
+
+        let hook_thread_id = thread_id_rx.recv().unwrap_or(0);
+        Self {
+            rx,
+            hook_thread_id,
+            hook_thread: Some(hook_thread),
+        }
+    }
+
+    /// 等待下一个窗口生命周期事件：(触发事件的句柄, 事件类型)
+    pub(crate) async fn recv(&mut self) -> Option<(isize, WindowLifecycleEvent)> {
+        self.rx.recv().await
+    }
+}
+
+impl Drop for WindowEventListener {
+    fn drop(&mut self) {
+        if self.hook_thread_id != 0 {
+            unsafe {
+                let _ = PostThreadMessageW(self.hook_thread_id, WM_QUIT, WPARAM(0), LPARAM(0));
+            }
+        }
+        if let Some(handle) = self.hook_thread.take() {
+            let _ = handle.join();
+        }
+    }
+}
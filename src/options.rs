@@ -0,0 +1,70 @@
+//! 截图选项：是否包含鼠标光标、是否绘制黄色捕获边框、输出的颜色格式、是否开启重复帧抑制。
+//! 以前`start`里这几项都是硬编码的`Default`，OCR/模板匹配这类消费者没法去掉污染画面的光标和边框。
+
+use std::time::Duration;
+
+use windows_capture::settings::{ColorFormat as WgcColorFormat, CursorCaptureSettings, DrawBorderSettings};
+
+/// 输出的颜色通道顺序
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum ColorFormat {
+    /// 红绿蓝透明，大多数图像处理库期望的顺序
+    #[default]
+    Rgba8,
+    /// 蓝绿红透明
+    Bgra8,
+}
+
+impl ColorFormat {
+    pub(crate) fn to_wgc(self) -> WgcColorFormat {
+        match self {
+            ColorFormat::Rgba8 => WgcColorFormat::Rgba8,
+            ColorFormat::Bgra8 => WgcColorFormat::Bgra8,
+        }
+    }
+}
+
+/// 截图选项
+#[derive(Debug, Clone, Copy)]
+pub struct CaptureOptions {
+    /// 是否在画面中包含鼠标光标（仅Windows.Graphics.Capture后端支持，BitBlt/PrintWindow后端的画面中不含光标）
+    pub include_cursor: bool,
+    /// 是否绘制黄色的捕获边框（仅Windows.Graphics.Capture后端支持）
+    pub draw_border: bool,
+    /// 输出图像的颜色通道顺序
+    pub color_format: ColorFormat,
+    /// 是否开启重复帧抑制：画面没有实际变化时跳过发送，只唤醒真正关心画面变化的消费者
+    pub dedupe: bool,
+    /// 开启`dedupe`后，即使画面没有变化也会按这个间隔发送一次，避免`get_img`误报"图像已过期"
+    pub heartbeat_interval: Duration,
+}
+
+impl Default for CaptureOptions {
+    fn default() -> Self {
+        Self {
+            include_cursor: true,
+            draw_border: true,
+            color_format: ColorFormat::Rgba8,
+            dedupe: false,
+            heartbeat_interval: Duration::from_secs(1),
+        }
+    }
+}
+
+impl CaptureOptions {
+    pub(crate) fn cursor_capture_settings(&self) -> CursorCaptureSettings {
+        if self.include_cursor {
+            CursorCaptureSettings::WithCursor
+        } else {
+            CursorCaptureSettings::WithoutCursor
+        }
+    }
+
+    pub(crate) fn draw_border_settings(&self) -> DrawBorderSettings {
+        if self.draw_border {
+            DrawBorderSettings::WithBorder
+        } else {
+            DrawBorderSettings::WithoutBorder
+        }
+    }
+}
@@ -0,0 +1,121 @@
+use std::collections::HashMap;
+use std::time::Duration;
+
+use anyhow::{anyhow, Result};
+use image::DynamicImage;
+
+use crate::{CaptureBackend, CaptureOptions, ClientCapture};
+
+/// 多窗口截图注册表
+/// 用一个名字管理多个 [`ClientCapture`]，避免每个使用者都自己维护一个
+/// `HashMap<String, ClientCapture>`，也提供了一个统一暂停/恢复所有截图的地方。
+#[derive(Default)]
+pub struct CaptureService {
+    captures: HashMap<String, ClientCapture>,
+}
+
+impl CaptureService {
+    /// 创建一个空的截图注册表
+    pub fn new() -> Self {
+        Self {
+            captures: HashMap::new(),
+        }
+    }
+
+    /// 注册一个新的截图任务并启动
+    /// # 参数
+    /// - name: 用来索引这个截图任务的名字
+    /// - window_class: 窗口类名
+    /// - window_title: 窗口标题
+    /// - border: 在客户区域的基础上额外需要去除的边框：左上右下
+    /// - delay: 可以接受的截图延时，默认50ms
+    /// - backend: 截图后端，默认`CaptureBackend::Auto`
+    /// - options: 光标/边框/颜色格式等截图选项，默认见[`CaptureOptions::default`]
+    /// # 返回
+    /// - Ok: 成功
+    /// - Err: 如果这个名字已经注册过，则返回错误
+    #[allow(clippy::too_many_arguments)]
+    pub fn register(
+        &mut self,
+        name: &str,
+        window_class: String,
+        window_title: String,
+        border: Option<(u32, u32, u32, u32)>,
+        delay: Option<Duration>,
+        backend: Option<CaptureBackend>,
+        options: Option<CaptureOptions>,
+    ) -> Result<()> {
+        if self.is_registered(name) {
+            return Err(anyhow!("已注册: {}", name));
+        }
+        let mut capture = ClientCapture::new(window_class, window_title, border, delay, backend, options);
+        capture.start()?;
+        self.captures.insert(name.to_string(), capture);
+        Ok(())
+    }
+
+    /// 注销一个截图任务，停止它的捕获线程
+    /// # 返回
+    /// - Ok: 成功
+    /// - Err: 如果这个名字没有注册过，则返回错误
+    pub fn unregister(&mut self, name: &str) -> Result<()> {
+        match self.captures.remove(name) {
+            Some(capture) => {
+                capture.stop();
+                Ok(())
+            }
+            None => Err(anyhow!("未注册: {}", name)),
+        }
+    }
+
+    /// 这个名字是否已经注册过
+    pub fn is_registered(&self, name: &str) -> bool {
+        self.captures.contains_key(name)
+    }
+
+    /// 更换已注册截图任务的目标窗口
+    /// # 返回
+    /// - Ok: 成功
+    /// - Err: 如果这个名字没有注册过，则返回错误
+    pub fn change_window(&mut self, name: &str, window_class: String, window_title: String) -> Result<()> {
+        let capture = self
+            .captures
+            .get_mut(name)
+            .ok_or_else(|| anyhow!("未注册: {}", name))?;
+        capture.change_window(window_class, window_title);
+        Ok(())
+    }
+
+    /// 获取指定名字的截图
+    /// # 返回
+    /// - Ok: 图像
+    /// - Err: 没有注册过，或者图像为空/已过期
+    pub fn get_img(&mut self, name: &str) -> Result<DynamicImage> {
+        let capture = self
+            .captures
+            .get_mut(name)
+            .ok_or_else(|| anyhow!("未注册: {}", name))?;
+        capture.get_img()
+    }
+
+    /// 暂停所有已注册的截图任务
+    pub fn pause_all(&self) {
+        for capture in self.captures.values() {
+            capture.pause();
+        }
+    }
+
+    /// 恢复所有已注册的截图任务
+    pub fn resume_all(&self) {
+        for capture in self.captures.values() {
+            capture.resume();
+        }
+    }
+
+    /// 停止所有已注册的截图任务，但不会把它们从注册表中移除
+    pub fn stop_all(&self) {
+        for capture in self.captures.values() {
+            capture.stop();
+        }
+    }
+}